@@ -1,9 +1,17 @@
 use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use minisign_verify::{PublicKey, Signature};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::LazyLock;
 use std::sync::Mutex;
+use tauri::Emitter;
+
+// 受信任的 minisign 公钥（随二进制内置，用于校验发布产物的签名）
+// 对应的私钥只在发布流程中使用，绝不提交到仓库
+const TRUSTED_PUBLIC_KEY: &str = "untrusted comment: minisign public key: 9B479D87517E97C9
+RWTJl35Rh51Hm2D7yYVfhGAbiuqZ0o3pLoBhxLgJmxxX3YlY7tNczVTK";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VersionInfo {
@@ -26,6 +34,22 @@ pub struct GitHubRelease {
     body: String,
 }
 
+// 每个目标平台对应的安装包及其 minisign 签名
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct RemotePlatformEntry {
+    url: String,
+    signature: String,
+}
+
+// 随每次发布一起发布的更新清单（latest.json），按目标三元组
+// （如 "darwin-aarch64"、"windows-x86_64"）索引平台特定的产物
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct RemoteRelease {
+    version: String,
+    pub_date: String,
+    platforms: HashMap<String, RemotePlatformEntry>,
+}
+
 // 缓存结构
 #[derive(Debug, Clone)]
 struct CachedRelease {
@@ -37,6 +61,77 @@ struct CachedRelease {
 static CACHE: LazyLock<Mutex<HashMap<String, CachedRelease>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
 
+// latest.json 更新清单的缓存：同一次检查 + 下载流程里，
+// `try_release_manifest` 和 `get_download_url_for_platform` 都需要
+// 完整的清单内容，缓存后避免同一周期内重复拉取
+#[derive(Debug, Clone)]
+struct CachedManifest {
+    manifest: RemoteRelease,
+    cached_at: DateTime<Utc>,
+}
+
+static MANIFEST_CACHE: LazyLock<Mutex<Option<CachedManifest>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+// 共享的 HTTP 客户端：统一连接/请求超时、重定向上限和 User-Agent，
+// 并复用连接池加速连续的探测 + 下载请求，避免每个函数各自
+// 新建一个不设超时的 reqwest::Client
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .timeout(std::time::Duration::from_secs(30))
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .user_agent("mirror-app")
+        .build()
+        .expect("failed to build shared HTTP client")
+});
+
+// 给重试延迟加一点抖动，避免多个客户端同时退避后又同时重试。
+// 不引入额外的随机数依赖，用系统时钟的纳秒位作为抖动源即可
+fn jittered_backoff_delay(attempt: u32) -> std::time::Duration {
+    let base_ms = 200u64 * 2u64.pow(attempt.saturating_sub(1));
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter_ms = nanos % (base_ms / 2 + 1);
+    std::time::Duration::from_millis(base_ms + jitter_ms)
+}
+
+// 版本比较策略：决定是否应该安装某个远程版本。
+// 允许灰度发布、beta 渠道、强制重装等场景，而不用改动比较逻辑本身
+type ShouldInstallPolicy = dyn Fn(&Version, &RemoteRelease) -> bool + Send + Sync;
+
+// 当前生效的安装策略，默认是“语义化版本号更大才安装”
+static UPDATE_POLICY: LazyLock<Mutex<Box<ShouldInstallPolicy>>> =
+    LazyLock::new(|| Mutex::new(Box::new(policy_semver_greater)));
+
+// 内置策略：仅当最新版本语义化大于当前版本时才安装
+fn policy_semver_greater(current: &Version, latest: &RemoteRelease) -> bool {
+    compare_versions(&current.to_string(), &latest.version)
+}
+
+// 内置策略：始终安装，用于强制重装
+fn policy_always(_current: &Version, _latest: &RemoteRelease) -> bool {
+    true
+}
+
+// 内置策略：永不自动安装，仅保留手动下载入口
+fn policy_never(_current: &Version, _latest: &RemoteRelease) -> bool {
+    false
+}
+
+// 内置策略：感知预发布版本。默认跳过带 "-beta" 后缀的版本，
+// 除非用户主动加入了 beta 渠道
+fn policy_prerelease_aware(beta_opt_in: bool) -> impl Fn(&Version, &RemoteRelease) -> bool {
+    move |current, latest| {
+        if latest.version.contains("-beta") && !beta_opt_in {
+            return false;
+        }
+        policy_semver_greater(current, latest)
+    }
+}
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -50,6 +145,27 @@ fn get_app_version() -> AppVersion {
     }
 }
 
+// 选择运行时生效的更新策略。`beta_opt_in` 只在 policy 为
+// "prerelease-aware" 时使用
+#[tauri::command]
+fn set_update_policy(policy: String, beta_opt_in: Option<bool>) -> Result<(), String> {
+    let new_policy: Box<ShouldInstallPolicy> = match policy.as_str() {
+        "semver-greater" => Box::new(policy_semver_greater),
+        "always" => Box::new(policy_always),
+        "never" => Box::new(policy_never),
+        "prerelease-aware" => Box::new(policy_prerelease_aware(beta_opt_in.unwrap_or(false))),
+        other => return Err(format!("未知的更新策略: {}", other)),
+    };
+
+    match UPDATE_POLICY.lock() {
+        Ok(mut guard) => {
+            *guard = new_policy;
+            Ok(())
+        }
+        Err(_) => Err("无法更新策略状态".to_string()),
+    }
+}
+
 #[tauri::command]
 async fn check_for_updates() -> Result<VersionInfo, String> {
     let current_version = env!("CARGO_PKG_VERSION");
@@ -57,12 +173,35 @@ async fn check_for_updates() -> Result<VersionInfo, String> {
     // 调用 GitHub API 获取最新版本
     match fetch_latest_release().await {
         Ok(release) => {
-            let latest_version = release.tag_name.trim_start_matches('v'); // 移除 'v' 前缀
-            let has_update = compare_versions(current_version, latest_version);
+            let latest_version = release.tag_name.trim_start_matches('v').to_string(); // 移除 'v' 前缀
+
+            // 尽量拿完整的 latest.json 清单传给策略判断，这样自定义
+            // should_install 能看到真实的 pub_date 和各平台产物，而不是
+            // 误导性的空数据；清单暂时不可用时（例如发布刚创建、
+            // latest.json 还没上传完）才退回到只有版本号的轻量对象
+            let remote_release = match fetch_release_manifest().await {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    eprintln!("无法获取完整更新清单，退回到仅版本号比较: {}", e);
+                    RemoteRelease {
+                        version: latest_version.clone(),
+                        pub_date: String::new(),
+                        platforms: HashMap::new(),
+                    }
+                }
+            };
+
+            let current_ver = Version::parse(current_version)
+                .map_err(|e| format!("Invalid current version: {}", e))?;
+
+            let has_update = match UPDATE_POLICY.lock() {
+                Ok(policy) => policy(&current_ver, &remote_release),
+                Err(_) => compare_versions(current_version, &latest_version),
+            };
 
             Ok(VersionInfo {
                 current: current_version.to_string(),
-                latest: latest_version.to_string(),
+                latest: latest_version,
                 has_update,
                 download_url: if has_update {
                     Some(release.html_url)
@@ -124,13 +263,35 @@ async fn fetch_latest_release() -> Result<GitHubRelease, Box<dyn std::error::Err
     Err("All update check methods failed. This could be due to network issues or GitHub API rate limits. Please try again later.".into())
 }
 
-// GitHub API方法
+// GitHub API方法，带指数退避重试，避免短暂的 5xx/429 直接
+// 退化到爬取页面的后备方案
 async fn try_github_api() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let response = client
+    const MAX_ATTEMPTS: u32 = 3;
+
+    let mut last_error = "GitHub API request failed".to_string();
+
+    for attempt in 1..=MAX_ATTEMPTS {
+        match try_github_api_once().await {
+            Ok(release) => return Ok(release),
+            Err(e) => {
+                eprintln!(
+                    "GitHub API attempt {}/{} failed: {}",
+                    attempt, MAX_ATTEMPTS, e
+                );
+                last_error = e.to_string();
+                if attempt < MAX_ATTEMPTS {
+                    tokio::time::sleep(jittered_backoff_delay(attempt)).await;
+                }
+            }
+        }
+    }
+
+    Err(last_error.into())
+}
+
+async fn try_github_api_once() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
+    let response = HTTP_CLIENT
         .get("https://api.github.com/repos/Godi13/mirror/releases/latest")
-        .header("User-Agent", "mirror-app")
-        .timeout(std::time::Duration::from_secs(10))
         .send()
         .await?;
 
@@ -156,7 +317,17 @@ async fn try_github_api() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
 
 // 后备方案：爬取GitHub releases页面
 async fn try_alternative_sources() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
-    // 方案A: 直接访问releases页面，解析HTML
+    // 方案A: 查询随发布一起发布的结构化 latest.json 清单。注意这仍然是
+    // GitHub 基础设施（releases 的静态资源），不是真正独立于 GitHub 的
+    // 数据源；但它走的是 release asset 下载路径，不经过 api.github.com，
+    // 所以不会占用和方案 GitHub API 共享的速率限制额度，在 API 限流时
+    // 仍然可用。这里不再声称“注册表”式的完全独立来源
+    match try_release_manifest().await {
+        Ok(release) => return Ok(release),
+        Err(e) => eprintln!("Release manifest lookup failed: {}", e),
+    }
+
+    // 方案B: 直接访问releases页面，解析HTML
     match try_scrape_releases_page().await {
         Ok(release) => return Ok(release),
         Err(e) => eprintln!("Scraping failed: {}", e),
@@ -165,13 +336,56 @@ async fn try_alternative_sources() -> Result<GitHubRelease, Box<dyn std::error::
     Err("All alternative sources failed".into())
 }
 
+// 拉取随每次发布一起发布的结构化 latest.json 更新清单，带 10 分钟缓存。
+// `try_release_manifest` 和 `get_download_url_for_platform` 在同一次
+// 检查 + 下载流程里都需要这份清单，共用这一个函数以避免重复拉取
+async fn fetch_release_manifest() -> Result<RemoteRelease, Box<dyn std::error::Error>> {
+    if let Ok(cache) = MANIFEST_CACHE.lock() {
+        if let Some(cached) = cache.as_ref() {
+            let ten_minutes_ago = Utc::now() - chrono::Duration::minutes(10);
+            if cached.cached_at > ten_minutes_ago {
+                println!("Using cached release manifest");
+                return Ok(cached.manifest.clone());
+            }
+        }
+    }
+
+    let manifest_url = "https://github.com/Godi13/mirror/releases/latest/download/latest.json";
+    let response = HTTP_CLIENT.get(manifest_url).send().await?;
+
+    if !response.status().is_success() {
+        return Err(format!("无法获取更新清单: HTTP {}", response.status()).into());
+    }
+
+    let manifest: RemoteRelease = response.json().await?;
+
+    if let Ok(mut cache) = MANIFEST_CACHE.lock() {
+        *cache = Some(CachedManifest {
+            manifest: manifest.clone(),
+            cached_at: Utc::now(),
+        });
+    }
+
+    Ok(manifest)
+}
+
+// 查询 latest.json 清单，直接拿到版本号，无需解析 HTML
+async fn try_release_manifest() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
+    let manifest = fetch_release_manifest().await?;
+    let version = manifest.version;
+
+    Ok(GitHubRelease {
+        tag_name: version.clone(),
+        html_url: format!("https://github.com/Godi13/mirror/releases/tag/v{}", version),
+        name: format!("Mirror {}", version),
+        body: "Retrieved from release manifest".to_string(),
+    })
+}
+
 // 爬取GitHub releases页面
 async fn try_scrape_releases_page() -> Result<GitHubRelease, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let response = client
+    let response = HTTP_CLIENT
         .get("https://github.com/Godi13/mirror/releases/latest")
-        .header("User-Agent", "mirror-app")
-        .timeout(std::time::Duration::from_secs(10))
         .send()
         .await?;
 
@@ -224,8 +438,16 @@ fn compare_versions(current: &str, latest: &str) -> bool {
     }
 }
 
+// 下载进度事件，推送给前端用于渲染进度条
+#[derive(Debug, Serialize, Clone)]
+struct UpdateDownloadProgress {
+    downloaded: u64,
+    total: u64,
+    percent: f64,
+}
+
 #[tauri::command]
-async fn trigger_update_check(_app: tauri::AppHandle) -> Result<String, String> {
+async fn trigger_update_check(app: tauri::AppHandle) -> Result<String, String> {
     println!("trigger_update_check 被调用");
 
     // 使用我们自己的更新检测逻辑
@@ -239,12 +461,22 @@ async fn trigger_update_check(_app: tauri::AppHandle) -> Result<String, String>
                 println!("发现更新: {}", result);
 
                 // 开始增量下载和安装过程
-                match download_and_install_update(version_info.clone()).await {
+                match download_and_install_update(&app, version_info.clone()).await {
                     Ok(install_result) => Ok(format!(
                         "{}
 {}",
                         result, install_result
                     )),
+                    Err(UpdateError::VerificationFailed(msg)) => {
+                        // 签名校验失败意味着产物可能被篡改，不应提供手动下载链接
+                        // 兜底，而是明确提示用户更新已被拒绝
+                        eprintln!("更新包签名校验失败: {}", msg);
+                        Err(format!(
+                            "{}
+更新包签名校验失败，已拒绝安装以保护您的设备: {}",
+                            result, msg
+                        ))
+                    }
                     Err(e) => {
                         // 如果自动安装失败，提供手动下载链接
                         if let Some(download_url) = version_info.download_url {
@@ -273,25 +505,121 @@ async fn trigger_update_check(_app: tauri::AppHandle) -> Result<String, String>
     }
 }
 
+// 更新流程中的错误类型。区分签名校验失败和其他错误，
+// 这样前端可以针对性地提示用户，而不是直接退化到手动下载链接
+#[derive(Debug)]
+enum UpdateError {
+    VerificationFailed(String),
+    Other(Box<dyn std::error::Error>),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateError::VerificationFailed(msg) => write!(f, "签名验证失败: {}", msg),
+            UpdateError::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for UpdateError {}
+
+impl From<Box<dyn std::error::Error>> for UpdateError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        UpdateError::Other(e)
+    }
+}
+
+impl From<std::io::Error> for UpdateError {
+    fn from(e: std::io::Error) -> Self {
+        UpdateError::Other(e.into())
+    }
+}
+
+impl From<reqwest::Error> for UpdateError {
+    fn from(e: reqwest::Error) -> Self {
+        UpdateError::Other(e.into())
+    }
+}
+
+// 校验下载产物的 minisign 签名。优先使用更新清单里自带的签名，
+// 没有清单时退回到约定的 "同一 URL + .sig" 路径。
+// 用流式校验逐块读取文件，不把整个安装包再次读进内存
+// （呼应 chunk0-2 里流式下载的初衷）
+async fn verify_update_signature(
+    download_url: &str,
+    signature: Option<&str>,
+    file_path: &std::path::Path,
+) -> Result<(), UpdateError> {
+    use std::io::Read;
+
+    let public_key = PublicKey::decode(TRUSTED_PUBLIC_KEY)
+        .map_err(|e| UpdateError::VerificationFailed(format!("内置公钥无效: {}", e)))?;
+
+    let sig_text = match signature {
+        Some(sig) => sig.to_string(),
+        None => {
+            let sig_url = format!("{}.sig", download_url);
+            let response = HTTP_CLIENT.get(&sig_url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(UpdateError::VerificationFailed(format!(
+                    "未找到签名文件: {}",
+                    sig_url
+                )));
+            }
+
+            response.text().await?
+        }
+    };
+
+    let signature = Signature::decode(&sig_text)
+        .map_err(|e| UpdateError::VerificationFailed(format!("签名格式无效: {}", e)))?;
+
+    let mut verifier = public_key
+        .verify_stream(&signature)
+        .map_err(|e| UpdateError::VerificationFailed(format!("签名不支持流式校验: {}", e)))?;
+
+    let mut file = std::fs::File::open(file_path)?;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        verifier.update(&buf[..read]);
+    }
+
+    verifier
+        .finalize()
+        .map_err(|e| UpdateError::VerificationFailed(format!("签名不匹配: {}", e)))?;
+
+    println!("更新包签名验证通过");
+    Ok(())
+}
+
 // 下载和安装更新的核心函数
 async fn download_and_install_update(
+    app: &tauri::AppHandle,
     version_info: VersionInfo,
-) -> Result<String, Box<dyn std::error::Error>> {
+) -> Result<String, UpdateError> {
     println!("开始下载更新...");
 
-    // 1. 确定下载URL
-    let download_url = match version_info.download_url {
+    // 1. 确定下载URL和签名
+    let (download_url, signature) = match version_info.download_url {
         Some(url) => {
             // 将GitHub releases页面URL转换为实际的下载URL
             if url.contains("/releases/tag/") {
-                // 根据平台选择合适的文件
+                // 根据平台从更新清单中查找对应产物
                 let platform = detect_platform();
-                get_download_url_for_platform(&version_info.latest, &platform).await?
+                let artifact =
+                    get_download_url_for_platform(&version_info.latest, &platform).await?;
+                (artifact.url, Some(artifact.signature))
             } else {
-                url
+                (url, None)
             }
         }
-        None => return Err("没有找到下载URL".into()),
+        None => return Err(UpdateError::Other("没有找到下载URL".into())),
     };
 
     println!("下载URL: {}", download_url);
@@ -305,11 +633,17 @@ async fn download_and_install_update(
     let file_path = temp_dir.join(file_name);
 
     println!("正在下载到: {:?}", file_path);
-    download_file(&download_url, &file_path).await?;
+    download_file(app, &download_url, &file_path).await?;
 
     // 4. 验证下载文件
     if !file_path.exists() {
-        return Err("下载文件不存在".into());
+        return Err(UpdateError::Other("下载文件不存在".into()));
+    }
+
+    // 4.1 校验 minisign 签名，失败则删除临时文件并中止安装
+    if let Err(e) = verify_update_signature(&download_url, signature.as_deref(), &file_path).await {
+        let _ = std::fs::remove_file(&file_path);
+        return Err(e);
     }
 
     // 5. 安装更新（这里我们启动安装程序，而不是直接替换）
@@ -320,7 +654,7 @@ async fn download_and_install_update(
     ))
 }
 
-// 检测当前平台
+// 检测当前 CPU 架构
 fn detect_platform() -> String {
     #[cfg(target_arch = "aarch64")]
     {
@@ -328,56 +662,99 @@ fn detect_platform() -> String {
     }
     #[cfg(target_arch = "x86_64")]
     {
-        "x64".to_string()
+        "x86_64".to_string()
     }
     #[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
     {
-        "x64".to_string() // 默认
+        "x86_64".to_string() // 默认
     }
 }
 
-// 根据平台获取下载URL
-async fn get_download_url_for_platform(
-    version: &str,
-    platform: &str,
-) -> Result<String, Box<dyn std::error::Error>> {
-    // 根据你的发布文件名规则构造URL
-    let file_name = if platform == "aarch64" {
-        format!("mirror_0.1.0_aarch64.dmg")
-    } else {
-        format!("mirror_0.1.0_x64.dmg")
-    };
+// latest.json 里操作系统部分用的是 Rust 之外的习惯命名（例如 macOS 叫
+// "darwin"），这里做一次映射；其余系统名称直接透传
+fn normalize_os_name(os: &str) -> &str {
+    match os {
+        "macos" => "darwin",
+        other => other,
+    }
+}
 
-    let url = format!(
-        "https://github.com/Godi13/mirror/releases/download/{}/{}",
-        version, file_name
-    );
+// 构造更新清单里使用的目标三元组键，例如 "darwin-aarch64"、"windows-x86_64"。
+// `os` 单独作为参数而不是直接读 std::env::consts::OS，方便在测试里覆盖
+fn build_target_key(os: &str, arch: &str) -> String {
+    format!("{}-{}", normalize_os_name(os), arch)
+}
 
-    // 验证URL是否有效
-    let client = reqwest::Client::new();
-    let response = client.head(&url).send().await?;
+// 从更新清单中为当前目标解析出来的可下载产物（包含签名）
+struct ResolvedArtifact {
+    url: String,
+    signature: String,
+}
 
-    if response.status().is_success() {
-        Ok(url)
-    } else {
-        Err(format!("找不到平台 {} 的安装包", platform).into())
+// 根据平台获取下载URL。从随发布一起发布的 latest.json 更新清单中
+// 查找当前目标三元组对应的产物，而不是硬编码版本号和文件名
+async fn get_download_url_for_platform(
+    version: &str,
+    platform: &str,
+) -> Result<ResolvedArtifact, Box<dyn std::error::Error>> {
+    let manifest = fetch_release_manifest().await?;
+    if manifest.version != version {
+        eprintln!(
+            "更新清单版本 {} 与目标版本 {} 不一致，继续使用清单内容",
+            manifest.version, version
+        );
     }
+
+    let target_key = build_target_key(std::env::consts::OS, platform);
+    manifest
+        .platforms
+        .get(&target_key)
+        .map(|entry| ResolvedArtifact {
+            url: entry.url.clone(),
+            signature: entry.signature.clone(),
+        })
+        .ok_or_else(|| format!("没有适用于目标 {} 的构建产物", target_key).into())
 }
 
-// 下载文件的函数
+// 下载文件的函数。边下载边写入磁盘，并通过事件上报进度，
+// 避免把整个安装包缓冲进内存后才开始写盘
 async fn download_file(
+    app: &tauri::AppHandle,
     url: &str,
     file_path: &std::path::Path,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let response = client.get(url).send().await?;
+    use std::io::Write;
+
+    let response = HTTP_CLIENT.get(url).send().await?;
 
     if !response.status().is_success() {
         return Err(format!("下载失败: HTTP {}", response.status()).into());
     }
 
-    let content = response.bytes().await?;
-    std::fs::write(file_path, content)?;
+    let total = response.content_length().unwrap_or(0);
+    let mut file = std::fs::File::create(file_path)?;
+    let mut downloaded: u64 = 0;
+    let mut stream = response.bytes_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk)?;
+        downloaded += chunk.len() as u64;
+
+        let percent = if total > 0 {
+            (downloaded as f64 / total as f64) * 100.0
+        } else {
+            0.0
+        };
+        let _ = app.emit(
+            "update-download-progress",
+            UpdateDownloadProgress {
+                downloaded,
+                total,
+                percent,
+            },
+        );
+    }
 
     println!(
         "文件下载完成: {:?} ({} 字节)",
@@ -388,6 +765,13 @@ async fn download_file(
 }
 
 // 安装更新的函数
+// 不同安装方式对调用方的约定不完全一样：
+// - 如果安装过程需要先释放当前进程对自身可执行文件的占用（Windows 上
+//   运行中的 exe/安装包会被系统锁定），这里会启动外部安装程序后直接
+//   `std::process::exit`，函数不会正常返回。
+// - 如果安装可以在当前进程运行的同时完成（Linux 的 AppImage 自替换、
+//   deb/rpm 交给包管理器），函数会同步完成安装并正常返回 `Ok(())`，
+//   新版本在应用重启后生效；调用方返回给用户的提示不区分这种情况。
 async fn install_update(file_path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
     println!("开始安装更新: {:?}", file_path);
 
@@ -405,13 +789,125 @@ async fn install_update(file_path: &std::path::Path) -> Result<(), Box<dyn std::
         Ok(())
     }
 
-    #[cfg(not(target_os = "macos"))]
+    #[cfg(target_os = "windows")]
+    {
+        install_update_windows(file_path)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        install_update_linux(file_path)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
-        // 在其他平台上，可以添加相应的安装逻辑
         Err("当前平台不支持自动安装".into())
     }
 }
 
+// Windows 平台安装：区分 .msi 和 .exe 安装程序，启动后退出当前进程
+// 以便安装程序可以替换正在运行的可执行文件
+#[cfg(target_os = "windows")]
+fn install_update_windows(
+    file_path: &std::path::Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let extension = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "msi" => {
+            // /passive 以非交互方式显示进度，避免用户需要手动点击下一步
+            std::process::Command::new("msiexec")
+                .args(["/i", &file_path.to_string_lossy(), "/passive"])
+                .spawn()?;
+        }
+        "exe" => {
+            // NSIS/Inno Setup 生成的安装程序，直接启动即可
+            std::process::Command::new(file_path).spawn()?;
+        }
+        other => {
+            return Err(format!("不支持的 Windows 安装包类型: .{}", other).into());
+        }
+    }
+
+    println!("安装程序已启动，即将退出当前进程以便安装程序替换文件");
+    std::process::exit(0);
+}
+
+// Linux 平台安装：AppImage 直接替换运行中的可执行文件，
+// .deb/.rpm 交给系统包管理器处理
+#[cfg(target_os = "linux")]
+fn install_update_linux(file_path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let extension = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    match extension.as_str() {
+        "appimage" => {
+            let mut perms = std::fs::metadata(file_path)?.permissions();
+            perms.set_mode(perms.mode() | 0o111);
+            std::fs::set_permissions(file_path, perms)?;
+
+            // 在 Linux 上替换一个正在运行的可执行文件的 inode 是安全的
+            // （进程会继续使用旧 inode 直到退出），所以这里直接把下载的
+            // AppImage 覆盖到当前 AppImage 文件的路径上，下次启动即生效。
+            // 注意不能用 std::env::current_exe()：AppImage 运行时会把
+            // 自身挂载到一个只读的 FUSE 挂载点下（/tmp/.mount_*/usr/bin/...），
+            // /proc/self/exe 解析到的是挂载点里的内部二进制，而不是磁盘上
+            // 真正的 .AppImage 文件；对它 rename/copy 要么因只读文件系统失败，
+            // 要么覆盖挂载点里的文件而不影响磁盘上的 AppImage。真正的路径由
+            // AppImage 运行时通过 APPIMAGE 环境变量提供，只有在它缺失时
+            // （例如开发环境下直接运行二进制）才退回到 current_exe()
+            let target_path = match std::env::var_os("APPIMAGE") {
+                Some(path) => std::path::PathBuf::from(path),
+                None => std::env::current_exe()?,
+            };
+
+            // 临时目录和安装目录通常不在同一文件系统，rename 可能因
+            // EXDEV 失败，这时退回到 copy + remove
+            if std::fs::rename(file_path, &target_path).is_err() {
+                std::fs::copy(file_path, &target_path)?;
+                std::fs::remove_file(file_path)?;
+            }
+
+            println!("AppImage 已替换为新版本，请重启应用以完成更新");
+            Ok(())
+        }
+        "deb" => {
+            let output = std::process::Command::new("pkexec")
+                .args(["dpkg", "-i"])
+                .arg(file_path)
+                .output()?;
+
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("dpkg 安装失败: {}", error).into());
+            }
+            Ok(())
+        }
+        "rpm" => {
+            let output = std::process::Command::new("pkexec")
+                .args(["rpm", "-U"])
+                .arg(file_path)
+                .output()?;
+
+            if !output.status.success() {
+                let error = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("rpm 安装失败: {}", error).into());
+            }
+            Ok(())
+        }
+        other => Err(format!("不支持的 Linux 安装包类型: .{}", other).into()),
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -421,8 +917,107 @@ pub fn run() {
             greet,
             get_app_version,
             check_for_updates,
-            trigger_update_check
+            trigger_update_check,
+            set_update_policy
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 用 TRUSTED_PUBLIC_KEY 对应的密钥对签出的测试向量，用来确认内置公钥
+    // 的编码格式正确，并且确实能通过 minisign-verify 校验
+    const KNOWN_GOOD_DATA: &[u8] = b"mirror test artifact bytes for minisign verification";
+    const KNOWN_GOOD_SIGNATURE: &str = "untrusted comment: signature from rsign secret key
+RUTJl35Rh51Hm23Pk5UGQappFzFRGXORAVtfnsjPx6swgzDvWXyQHFB+aTWtwtr7S79C9abUfvt40wLZSYCj6UCywVmjj4T5OgE=
+trusted comment: test vector for trusted public key
+nDXNV953yNAGpMe+2fRnFgWcL1xLtMkgl2PalvFWo3jRUG3fc0iEfYgMbwXolYW6tzzkSijpmsfxWKZ4oiVjDQ==
+";
+
+    #[test]
+    fn trusted_public_key_decodes_and_verifies_a_known_good_signature() {
+        let public_key =
+            PublicKey::decode(TRUSTED_PUBLIC_KEY).expect("TRUSTED_PUBLIC_KEY should decode");
+        let signature = Signature::decode(KNOWN_GOOD_SIGNATURE)
+            .expect("known-good signature should decode");
+
+        public_key
+            .verify(KNOWN_GOOD_DATA, &signature, false)
+            .expect("known-good signature should verify against TRUSTED_PUBLIC_KEY");
+    }
+
+    // `verify_update_signature` 实际走的是 verify_stream/update/finalize 这条
+    // 分块校验路径（见该函数），和上面一次性 verify() 的测试不是同一条代码
+    // 路径；这里用同一组测试向量，以同样的 64KB 分块方式重放一遍，确认
+    // 生产代码实际使用的流式 API 行为正确
+    #[test]
+    fn trusted_public_key_verifies_known_good_signature_via_streaming_api() {
+        use std::io::Read;
+
+        let public_key =
+            PublicKey::decode(TRUSTED_PUBLIC_KEY).expect("TRUSTED_PUBLIC_KEY should decode");
+        let signature = Signature::decode(KNOWN_GOOD_SIGNATURE)
+            .expect("known-good signature should decode");
+
+        let mut verifier = public_key
+            .verify_stream(&signature)
+            .expect("known-good signature should support streaming verification");
+
+        let mut cursor = KNOWN_GOOD_DATA;
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let read = cursor.read(&mut buf).expect("reading from a slice cannot fail");
+            if read == 0 {
+                break;
+            }
+            verifier.update(&buf[..read]);
+        }
+
+        verifier
+            .finalize()
+            .expect("streaming verification should pass for a known-good signature");
+    }
+
+    fn sample_release(version: &str) -> RemoteRelease {
+        RemoteRelease {
+            version: version.to_string(),
+            pub_date: String::new(),
+            platforms: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn policy_semver_greater_installs_only_strictly_newer_versions() {
+        let current = Version::parse("1.0.0").unwrap();
+
+        assert!(policy_semver_greater(&current, &sample_release("1.1.0")));
+        assert!(!policy_semver_greater(&current, &sample_release("1.0.0")));
+        assert!(!policy_semver_greater(&current, &sample_release("0.9.0")));
+    }
+
+    #[test]
+    fn policy_prerelease_aware_skips_beta_unless_opted_in() {
+        let current = Version::parse("1.0.0").unwrap();
+        let beta_release = sample_release("1.1.0-beta.1");
+
+        let stable_only = policy_prerelease_aware(false);
+        assert!(!stable_only(&current, &beta_release));
+
+        let beta_channel = policy_prerelease_aware(true);
+        assert!(beta_channel(&current, &beta_release));
+    }
+
+    #[test]
+    fn build_target_key_maps_macos_to_darwin() {
+        assert_eq!(build_target_key("macos", "aarch64"), "darwin-aarch64");
+    }
+
+    #[test]
+    fn build_target_key_passes_through_other_os_names() {
+        assert_eq!(build_target_key("linux", "x86_64"), "linux-x86_64");
+        assert_eq!(build_target_key("windows", "x86_64"), "windows-x86_64");
+    }
+}